@@ -1,4 +1,5 @@
 use clap::Parser;
+use serde::Serialize;
 use std::process::{Command, Stdio};
 use std::time::Instant;
 use std::sync::Arc;
@@ -8,13 +9,23 @@ use std::sync::atomic::{AtomicBool, Ordering};
 #[cfg(windows)]
 use winapi::um::processthreadsapi::{GetProcessTimes, OpenProcess};
 #[cfg(windows)]
+use winapi::um::winbase::GetProcessIoCounters;
+#[cfg(windows)]
 use winapi::shared::minwindef::FILETIME;
 #[cfg(windows)]
-use winapi::um::winnt::{HANDLE, PROCESS_QUERY_INFORMATION, PROCESS_QUERY_LIMITED_INFORMATION};
+use winapi::um::winnt::{
+    HANDLE, IO_COUNTERS, PROCESS_QUERY_INFORMATION, PROCESS_QUERY_LIMITED_INFORMATION,
+    JobObjectBasicAccountingInformation, JobObjectExtendedLimitInformation,
+    JOBOBJECT_BASIC_ACCOUNTING_INFORMATION, JOBOBJECT_EXTENDED_LIMIT_INFORMATION,
+};
 #[cfg(windows)]
 use winapi::um::handleapi::CloseHandle;
 #[cfg(windows)]
 use winapi::um::psapi::{GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS};
+#[cfg(windows)]
+use winapi::um::jobapi2::{AssignProcessToJobObject, CreateJobObjectW, QueryInformationJobObject};
+#[cfg(windows)]
+use std::os::windows::io::AsRawHandle;
 
 // Unix-specific imports  
 #[cfg(unix)]
@@ -27,7 +38,8 @@ use libc;
 #[command(about = "A cross-platform Unix-like time command")]
 #[command(version = "1.0")]
 struct Args {
-    /// Output format (currently only supports default format)
+    /// Output format string (GNU time syntax, e.g. "%e real, %U user, %S sys").
+    /// Overrides both the default and verbose output blocks.
     #[arg(short = 'f', long = "format")]
     format: Option<String>,
     
@@ -42,7 +54,19 @@ struct Args {
     /// Portable mode - use less accurate but more portable timing
     #[arg(short = 'p', long = "portable")]
     portable: bool,
-    
+
+    /// Emit all metrics as a single JSON object instead of human-readable text
+    #[arg(long = "json")]
+    json: bool,
+
+    /// Run the command N times and report aggregate statistics (mean/min/max/stddev)
+    #[arg(long = "runs")]
+    runs: Option<u32>,
+
+    /// Run the command K times before timing starts, to prime caches (only with --runs)
+    #[arg(long = "warmup", default_value_t = 0)]
+    warmup: u32,
+
     /// Command to execute (everything after the options)
     #[arg(trailing_var_arg = true, required = true)]
     command: Vec<String>,
@@ -52,8 +76,147 @@ struct Args {
 struct ResourceUsage {
     user_time: f64,
     system_time: f64,
-    #[allow(dead_code)] // May not be used on all platforms
     max_memory: u64, // in KB
+    // Extended accounting for the `-v` report. `None` means the platform
+    // doesn't expose the field (rendered as "N/A").
+    major_faults: Option<u64>,
+    minor_faults: Option<u64>,
+    voluntary_ctxsw: Option<u64>,
+    involuntary_ctxsw: Option<u64>,
+    fs_inputs: Option<u64>,
+    fs_outputs: Option<u64>,
+    signals: Option<u64>,
+}
+
+// All metrics collected for a single run, in serializable form. The JSON
+// output serializes this struct whole. The text outputs take only the command
+// line (and, in the verbose block, the CPU percentage) from it; they read the
+// timing and resource values from `ResourceUsage` and the wall time directly.
+#[derive(Serialize)]
+struct Metrics {
+    command: Vec<String>,
+    exit_code: Option<i32>,
+    interrupted: bool,
+    elapsed_seconds: f64,
+    user_seconds: f64,
+    system_seconds: f64,
+    cpu_percent: f64,
+    peak_memory_kb: u64,
+    major_faults: Option<u64>,
+    minor_faults: Option<u64>,
+    voluntary_ctxsw: Option<u64>,
+    involuntary_ctxsw: Option<u64>,
+    fs_inputs: Option<u64>,
+    fs_outputs: Option<u64>,
+    signals: Option<u64>,
+}
+
+impl Metrics {
+    fn new(
+        command: &[String],
+        exit_status: &std::process::ExitStatus,
+        interrupted: bool,
+        wall_seconds: f64,
+        usage: &ResourceUsage,
+    ) -> Self {
+        let cpu_percent = if wall_seconds > 0.0 {
+            (usage.user_time + usage.system_time) / wall_seconds * 100.0
+        } else {
+            0.0
+        };
+
+        Metrics {
+            command: command.to_vec(),
+            exit_code: exit_status.code(),
+            interrupted,
+            elapsed_seconds: wall_seconds,
+            user_seconds: usage.user_time,
+            system_seconds: usage.system_time,
+            cpu_percent,
+            peak_memory_kb: usage.max_memory,
+            major_faults: usage.major_faults,
+            minor_faults: usage.minor_faults,
+            voluntary_ctxsw: usage.voluntary_ctxsw,
+            involuntary_ctxsw: usage.involuntary_ctxsw,
+            fs_inputs: usage.fs_inputs,
+            fs_outputs: usage.fs_outputs,
+            signals: usage.signals,
+        }
+    }
+}
+
+// Running sample statistics (mean/min/max/stddev) computed with Welford's
+// online algorithm, so memory use doesn't grow with the number of runs.
+#[derive(Clone, Copy)]
+struct Stats {
+    n: u64,
+    mean: f64,
+    m2: f64,
+    min: f64,
+    max: f64,
+}
+
+impl Stats {
+    fn new() -> Self {
+        Stats {
+            n: 0,
+            mean: 0.0,
+            m2: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    fn add(&mut self, x: f64) {
+        self.n += 1;
+        let delta = x - self.mean;
+        self.mean += delta / self.n as f64;
+        self.m2 += delta * (x - self.mean);
+        self.min = self.min.min(x);
+        self.max = self.max.max(x);
+    }
+
+    fn stddev(&self) -> f64 {
+        if self.n > 1 {
+            (self.m2 / (self.n - 1) as f64).sqrt()
+        } else {
+            0.0
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct AggregateStats {
+    mean: f64,
+    min: f64,
+    max: f64,
+    stddev: f64,
+}
+
+impl From<Stats> for AggregateStats {
+    fn from(s: Stats) -> Self {
+        AggregateStats {
+            mean: s.mean,
+            min: if s.n > 0 { s.min } else { 0.0 },
+            max: if s.n > 0 { s.max } else { 0.0 },
+            stddev: s.stddev(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct RunRecord {
+    wall_seconds: f64,
+    user_seconds: f64,
+    system_seconds: f64,
+}
+
+#[derive(Serialize)]
+struct RunsReport {
+    runs: Vec<RunRecord>,
+    wall: AggregateStats,
+    user: AggregateStats,
+    system: AggregateStats,
 }
 
 // Windows implementation
@@ -116,55 +279,152 @@ fn get_child_process_times(child_id: u32) -> Result<ResourceUsage, Box<dyn std::
             max_memory_kb = mem_counters.PeakWorkingSetSize as u64 / 1024;
         }
         
+        let (fs_inputs, fs_outputs) = get_process_io_counts(handle);
+
         CloseHandle(handle);
-        
+
         if timing_result == 0 {
             // Even if timing fails, we might have memory info
             return Ok(ResourceUsage {
                 user_time: 0.0,
                 system_time: 0.0,
                 max_memory: max_memory_kb,
+                minor_faults: Some(mem_counters.PageFaultCount as u64),
+                fs_inputs,
+                fs_outputs,
+                ..ResourceUsage::default()
             });
         }
-        
+
         let user_seconds = filetime_to_seconds(&user_time);
         let kernel_seconds = filetime_to_seconds(&kernel_time);
-        
+
         Ok(ResourceUsage {
             user_time: user_seconds,
             system_time: kernel_seconds,
             max_memory: max_memory_kb,
+            minor_faults: Some(mem_counters.PageFaultCount as u64),
+            fs_inputs,
+            fs_outputs,
+            ..ResourceUsage::default()
+        })
+    }
+}
+
+// Approximates filesystem input/output counts from GetProcessIoCounters.
+// The Win32 API has no major/minor fault split and no context-switch counts,
+// so callers report the total page-fault count as `minor_faults` and leave
+// the major-fault and context-switch fields `None`.
+#[cfg(windows)]
+fn get_process_io_counts(handle: HANDLE) -> (Option<u64>, Option<u64>) {
+    let mut io_counters: IO_COUNTERS = unsafe { std::mem::zeroed() };
+    if unsafe { GetProcessIoCounters(handle, &mut io_counters) } != 0 {
+        (
+            Some(io_counters.ReadOperationCount),
+            Some(io_counters.WriteOperationCount),
+        )
+    } else {
+        (None, None)
+    }
+}
+
+// Reads aggregate CPU/memory accounting for every process in a Job Object,
+// not just the immediate child - this covers grandchildren spawned by shell
+// scripts or build wrappers, matching Unix RUSAGE_CHILDREN semantics.
+#[cfg(windows)]
+fn get_job_resource_usage(job: HANDLE) -> Result<ResourceUsage, Box<dyn std::error::Error>> {
+    unsafe {
+        let mut accounting: JOBOBJECT_BASIC_ACCOUNTING_INFORMATION = std::mem::zeroed();
+        let accounting_ok = QueryInformationJobObject(
+            job,
+            JobObjectBasicAccountingInformation,
+            &mut accounting as *mut _ as *mut _,
+            std::mem::size_of::<JOBOBJECT_BASIC_ACCOUNTING_INFORMATION>() as u32,
+            std::ptr::null_mut(),
+        );
+        if accounting_ok == 0 {
+            return Err("QueryInformationJobObject(JobObjectBasicAccountingInformation) failed".into());
+        }
+
+        // TotalUserTime/TotalKernelTime are 100-nanosecond LARGE_INTEGERs, same
+        // units as FILETIME, so we reuse filetime_to_seconds' /10_000_000 math.
+        let user_time = *accounting.TotalUserTime.QuadPart() as f64 / 10_000_000.0;
+        let system_time = *accounting.TotalKernelTime.QuadPart() as f64 / 10_000_000.0;
+
+        let mut limits: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = std::mem::zeroed();
+        let (peak_memory_kb, fs_inputs, fs_outputs) = if QueryInformationJobObject(
+            job,
+            JobObjectExtendedLimitInformation,
+            &mut limits as *mut _ as *mut _,
+            std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+            std::ptr::null_mut(),
+        ) != 0
+        {
+            (
+                limits.PeakJobMemoryUsed as u64 / 1024,
+                Some(limits.IoInfo.ReadOperationCount),
+                Some(limits.IoInfo.WriteOperationCount),
+            )
+        } else {
+            (0, None, None)
+        };
+
+        Ok(ResourceUsage {
+            user_time,
+            system_time,
+            max_memory: peak_memory_kb,
+            minor_faults: Some(accounting.TotalPageFaultCount as u64),
+            fs_inputs,
+            fs_outputs,
+            ..ResourceUsage::default()
         })
     }
 }
 
 // Unix implementation (Linux, macOS, etc.)
+//
+// `getrusage(RUSAGE_CHILDREN, ...)` is process-global and cumulative: it
+// aggregates every child the calling process has *ever* reaped, so it can't
+// tell one child's usage apart from another's when `time` spawns more than
+// one (as `--runs`/`--warmup` do). Instead we reap the child ourselves with
+// `wait4(pid, ...)`, whose rusage output parameter is scoped to exactly the
+// child being waited on - this gives per-child accounting atomically with
+// reaping it, with no /proc entry to race against, on every Unix.
 #[cfg(unix)]
-fn get_child_process_times(child_id: u32) -> Result<ResourceUsage, Box<dyn std::error::Error>> {
-    use std::fs;
-    
-    // Try to read from /proc/[pid]/stat (Linux)
-    if let Ok(stat_content) = fs::read_to_string(format!("/proc/{}/stat", child_id)) {
-        let fields: Vec<&str> = stat_content.split_whitespace().collect();
-        if fields.len() >= 24 {
-            // Fields 13 and 14 are utime and stime in clock ticks
-            let utime: u64 = fields[13].parse().unwrap_or(0);
-            let stime: u64 = fields[14].parse().unwrap_or(0);
-            let clock_ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) } as f64;
-            
-            // Field 23 is vsize (virtual memory size)
-            let vsize: u64 = fields[22].parse().unwrap_or(0);
-            
-            return Ok(ResourceUsage {
-                user_time: utime as f64 / clock_ticks,
-                system_time: stime as f64 / clock_ticks,
-                max_memory: vsize / 1024, // Convert to KB
-            });
-        }
+fn wait4_child(pid: u32) -> Result<(std::process::ExitStatus, ResourceUsage), Box<dyn std::error::Error>> {
+    use std::os::unix::process::ExitStatusExt;
+
+    let mut status: libc::c_int = 0;
+    let mut rusage: libc::rusage = unsafe { std::mem::zeroed() };
+
+    let ret = unsafe { libc::wait4(pid as libc::pid_t, &mut status, 0, &mut rusage) };
+    if ret < 0 {
+        return Err(Box::new(std::io::Error::last_os_error()));
     }
-    
-    // Fallback: use rusage (works on macOS and other Unix systems)
-    Ok(ResourceUsage::default())
+
+    let user_time = rusage.ru_utime.tv_sec as f64 + rusage.ru_utime.tv_usec as f64 / 1_000_000.0;
+    let system_time = rusage.ru_stime.tv_sec as f64 + rusage.ru_stime.tv_usec as f64 / 1_000_000.0;
+
+    // ru_maxrss is in kilobytes on Linux, but in *bytes* on macOS/BSD.
+    #[cfg(any(target_os = "macos", target_os = "ios", target_os = "freebsd", target_os = "openbsd", target_os = "netbsd", target_os = "dragonfly"))]
+    let max_memory = (rusage.ru_maxrss as u64) / 1024;
+    #[cfg(not(any(target_os = "macos", target_os = "ios", target_os = "freebsd", target_os = "openbsd", target_os = "netbsd", target_os = "dragonfly")))]
+    let max_memory = rusage.ru_maxrss as u64;
+
+    let usage = ResourceUsage {
+        user_time,
+        system_time,
+        max_memory,
+        major_faults: Some(rusage.ru_majflt as u64),
+        minor_faults: Some(rusage.ru_minflt as u64),
+        voluntary_ctxsw: Some(rusage.ru_nvcsw as u64),
+        involuntary_ctxsw: Some(rusage.ru_nivcsw as u64),
+        fs_inputs: Some(rusage.ru_inblock as u64),
+        fs_outputs: Some(rusage.ru_oublock as u64),
+        signals: Some(rusage.ru_nsignals as u64),
+    };
+
+    Ok((std::process::ExitStatus::from_raw(status), usage))
 }
 
 fn format_time(seconds: f64) -> String {
@@ -190,6 +450,76 @@ fn format_memory(kb: u64) -> String {
     }
 }
 
+// Renders a GNU `time -f`-style format string, substituting `%`-escapes with
+// the measured metrics. Unknown `%` specifiers and literal `\n`/`\t` escapes
+// are handled the same way GNU time handles them: unknown specifiers are
+// passed through verbatim rather than erroring.
+fn render_format(
+    fmt: &str,
+    command_str: &str,
+    wall_seconds: f64,
+    usage: &ResourceUsage,
+    exit_status: &std::process::ExitStatus,
+) -> String {
+    let mut out = String::with_capacity(fmt.len());
+    let mut chars = fmt.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '%' => match chars.next() {
+                Some('e') => out.push_str(&format!("{:.2}", wall_seconds)),
+                Some('E') => out.push_str(&format_elapsed_hms(wall_seconds)),
+                Some('U') => out.push_str(&format!("{:.2}", usage.user_time)),
+                Some('S') => out.push_str(&format!("{:.2}", usage.system_time)),
+                Some('P') => {
+                    let cpu_percent = if wall_seconds > 0.0 {
+                        (usage.user_time + usage.system_time) / wall_seconds * 100.0
+                    } else {
+                        0.0
+                    };
+                    out.push_str(&format!("{:.0}%", cpu_percent));
+                }
+                Some('M') => out.push_str(&usage.max_memory.to_string()),
+                Some('C') => out.push_str(command_str),
+                Some('x') => out.push_str(&exit_status.code().unwrap_or(-1).to_string()),
+                Some('%') => out.push('%'),
+                Some(other) => {
+                    out.push('%');
+                    out.push(other);
+                }
+                None => out.push('%'),
+            },
+            '\\' => match chars.next() {
+                Some('n') => out.push('\n'),
+                Some('t') => out.push('\t'),
+                Some(other) => {
+                    out.push('\\');
+                    out.push(other);
+                }
+                None => out.push('\\'),
+            },
+            _ => out.push(c),
+        }
+    }
+
+    out
+}
+
+// Formats elapsed seconds as GNU time's `%E` does: `[h:]m:ss.cc`.
+fn format_elapsed_hms(seconds: f64) -> String {
+    let total_centis = (seconds * 100.0).round() as u64;
+    let hours = total_centis / 100 / 3600;
+    let minutes = (total_centis / 100 / 60) % 60;
+    let secs = (total_centis / 100) % 60;
+    let centis = total_centis % 100;
+
+    if hours > 0 {
+        format!("{}:{:02}:{:02}.{:02}", hours, minutes, secs, centis)
+    } else {
+        format!("{}:{:02}.{:02}", minutes, secs, centis)
+    }
+}
+
 fn execute_and_measure(args: &Args, interrupted: Arc<AtomicBool>) -> Result<(std::process::ExitStatus, f64, ResourceUsage, bool), Box<dyn std::error::Error>> {
     let program = &args.command[0];
     let program_args = if args.command.len() > 1 {
@@ -229,6 +559,11 @@ fn execute_platform_optimized(
     wall_start: Instant,
     interrupted: Arc<AtomicBool>,
 ) -> Result<(std::process::ExitStatus, f64, ResourceUsage, bool), Box<dyn std::error::Error>> {
+    // Create the Job Object *before* spawning so the child can be assigned to
+    // it the moment it exists - this ties accounting to the job rather than
+    // to a PID, which the OS is free to recycle once the child is reaped.
+    let job = unsafe { CreateJobObjectW(std::ptr::null_mut(), std::ptr::null()) };
+
     let mut child = Command::new(program)
         .args(args)
         .stdin(Stdio::inherit())
@@ -236,14 +571,28 @@ fn execute_platform_optimized(
         .stderr(Stdio::inherit())
         .spawn()
         .map_err(|e| format!("Failed to execute '{}': {}", program, e))?;
-    
+
+    let job_assigned = !job.is_null()
+        && unsafe { AssignProcessToJobObject(job, child.as_raw_handle() as HANDLE) != 0 };
+
     let child_id = child.id();
     let exit_status = child.wait()?;
     let wall_elapsed = wall_start.elapsed().as_secs_f64();
     let was_interrupted = interrupted.load(Ordering::SeqCst);
-    
-    let resource_usage = get_child_process_times(child_id).unwrap_or_default();
-    
+
+    // Prefer the Job Object's aggregate accounting; fall back to the
+    // single-process GetProcessTimes path if the job couldn't be created or
+    // the child couldn't be assigned to it.
+    let resource_usage = if job_assigned {
+        get_job_resource_usage(job).unwrap_or_else(|_| get_child_process_times(child_id).unwrap_or_default())
+    } else {
+        get_child_process_times(child_id).unwrap_or_default()
+    };
+
+    if !job.is_null() {
+        unsafe { CloseHandle(job) };
+    }
+
     Ok((exit_status, wall_elapsed, resource_usage, was_interrupted))
 }
 
@@ -254,24 +603,121 @@ fn execute_platform_optimized(
     wall_start: Instant,
     interrupted: Arc<AtomicBool>,
 ) -> Result<(std::process::ExitStatus, f64, ResourceUsage, bool), Box<dyn std::error::Error>> {
-    let mut child = Command::new(program)
+    let child = Command::new(program)
         .args(args)
         .stdin(Stdio::inherit())
         .stdout(Stdio::inherit())
         .stderr(Stdio::inherit())
         .spawn()
         .map_err(|e| format!("Failed to execute '{}': {}", program, e))?;
-    
-    let child_id = child.id();
-    let exit_status = child.wait()?;
+
+    // Reap the child ourselves via wait4 instead of Child::wait, so we get
+    // this child's rusage atomically with reaping it (see wait4_child).
+    let (exit_status, resource_usage) = wait4_child(child.id())?;
     let wall_elapsed = wall_start.elapsed().as_secs_f64();
     let was_interrupted = interrupted.load(Ordering::SeqCst);
-    
-    let resource_usage = get_child_process_times(child_id).unwrap_or_default();
-    
+
     Ok((exit_status, wall_elapsed, resource_usage, was_interrupted))
 }
 
+// Executes the command `runs` times (after `warmup` untimed priming runs)
+// and reports aggregate wall/user/system statistics instead of a single
+// measurement, which is what people actually want when timing short
+// commands whose single-shot measurement is dominated by noise. Stops
+// early and reports over whatever runs completed if interrupted.
+//
+// On Unix (outside portable mode), each run's user/system time comes from
+// wait4_child, which is scoped to
+// that one child - unlike RUSAGE_CHILDREN, it can't leak a prior run's (or
+// a warmup run's) usage into the next, so the series being aggregated here
+// is genuinely independent samples rather than a running cumulative total.
+fn run_aggregate_mode(
+    args: &Args,
+    runs: u32,
+    interrupted: Arc<AtomicBool>,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    for i in 0..args.warmup {
+        if interrupted.load(Ordering::SeqCst) {
+            break;
+        }
+        if args.verbose {
+            eprintln!("Warmup run {}/{}", i + 1, args.warmup);
+        }
+        execute_and_measure(args, interrupted.clone())?;
+    }
+
+    let mut wall_stats = Stats::new();
+    let mut user_stats = Stats::new();
+    let mut system_stats = Stats::new();
+    let mut records = Vec::new();
+    let mut was_interrupted = false;
+
+    for i in 0..runs {
+        if interrupted.load(Ordering::SeqCst) {
+            was_interrupted = true;
+            break;
+        }
+
+        let (_, wall_seconds, resource_usage, run_interrupted) = execute_and_measure(args, interrupted.clone())?;
+
+        wall_stats.add(wall_seconds);
+        user_stats.add(resource_usage.user_time);
+        system_stats.add(resource_usage.system_time);
+        records.push(RunRecord {
+            wall_seconds,
+            user_seconds: resource_usage.user_time,
+            system_seconds: resource_usage.system_time,
+        });
+
+        if args.verbose {
+            eprintln!("Run {}/{}: {}", i + 1, runs, format_time(wall_seconds));
+        }
+
+        if run_interrupted {
+            was_interrupted = true;
+            break;
+        }
+    }
+
+    let output = if args.json {
+        let report = RunsReport {
+            runs: records,
+            wall: wall_stats.into(),
+            user: user_stats.into(),
+            system: system_stats.into(),
+        };
+        format!("{}\n", serde_json::to_string(&report)?)
+    } else {
+        let row = |label: &str, stats: &Stats| {
+            format!(
+                "{:<6} mean {:>10}  min {:>10}  max {:>10}  stddev {:>10}",
+                label,
+                format_time(stats.mean),
+                format_time(if stats.n > 0 { stats.min } else { 0.0 }),
+                format_time(if stats.n > 0 { stats.max } else { 0.0 }),
+                format_time(stats.stddev()),
+            )
+        };
+
+        let lines = [
+            format!("Runs:  {}", records.len()),
+            row("wall", &wall_stats),
+            row("user", &user_stats),
+            row("sys", &system_stats),
+        ];
+
+        format!("\n{}\n", lines.join("\n"))
+    };
+
+    if let Some(output_file) = &args.output_file {
+        std::fs::write(output_file, output)?;
+    } else {
+        eprint!("{}", output);
+    }
+
+    Ok(was_interrupted)
+}
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::parse();
     
@@ -306,36 +752,52 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     }
     
+    if let Some(runs) = args.runs {
+        let was_interrupted = run_aggregate_mode(&args, runs, interrupted.clone())?;
+        std::process::exit(if was_interrupted { 130 } else { 0 });
+    }
+
     // Execute the command and measure resources
     let (exit_status, wall_seconds, resource_usage, was_interrupted) = execute_and_measure(&args, interrupted.clone())?;
-    
+
+    let metrics = Metrics::new(&args.command, &exit_status, was_interrupted, wall_seconds, &resource_usage);
+
     // Format timing information
-    let timing_info = if args.verbose {
-        let command_str = args.command.join(" ");
+    let timing_info = if args.json {
+        format!("{}\n", serde_json::to_string(&metrics)?)
+    } else if let Some(format) = &args.format {
+        let command_str = metrics.command.join(" ");
+        format!("{}\n", render_format(format, &command_str, wall_seconds, &resource_usage, &exit_status))
+    } else if args.verbose {
+        let command_str = metrics.command.join(" ");
         let exit_str = if was_interrupted {
             "interrupted".to_string()
         } else {
             exit_status.code().map_or("signal".to_string(), |c| c.to_string())
         };
-        let cpu_usage = if wall_seconds > 0.0 { 
-            ((resource_usage.user_time + resource_usage.system_time) / wall_seconds) * 100.0 
-        } else { 
-            0.0 
-        };
-        
+
         let mut lines = vec![
             format!("Command:             {}", command_str),
             format!("Exit status:         {}", exit_str),
             format!("Elapsed time:        {}", format_time(wall_seconds)),
             format!("User time:           {}", format_time(resource_usage.user_time)),
             format!("System time:         {}", format_time(resource_usage.system_time)),
-            format!("CPU usage:           {:.1}%", cpu_usage),
+            format!("CPU usage:           {:.1}%", metrics.cpu_percent),
         ];
-        
+
         if resource_usage.max_memory > 0 {
             lines.push(format!("Peak memory:         {}", format_memory(resource_usage.max_memory)));
         }
-        
+
+        let opt = |v: Option<u64>| v.map_or("N/A".to_string(), |v| v.to_string());
+        lines.push(format!("Major page faults:   {}", opt(resource_usage.major_faults)));
+        lines.push(format!("Minor page faults:   {}", opt(resource_usage.minor_faults)));
+        lines.push(format!("Voluntary context switches:   {}", opt(resource_usage.voluntary_ctxsw)));
+        lines.push(format!("Involuntary context switches: {}", opt(resource_usage.involuntary_ctxsw)));
+        lines.push(format!("File system inputs:  {}", opt(resource_usage.fs_inputs)));
+        lines.push(format!("File system outputs: {}", opt(resource_usage.fs_outputs)));
+        lines.push(format!("Signals delivered:   {}", opt(resource_usage.signals)));
+
         format!("\n{}\n", lines.join("\n"))
     } else {
         // Standard Unix time format - exactly like real time command
@@ -346,7 +808,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             format_time(resource_usage.system_time)
         )
     };
-    
+
     // Output timing information - always show it, even if interrupted
     if let Some(output_file) = args.output_file {
         std::fs::write(output_file, timing_info)?;
@@ -364,3 +826,105 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         std::process::exit(1);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_exit_status(code: i32) -> std::process::ExitStatus {
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::ExitStatusExt;
+            std::process::ExitStatus::from_raw(code << 8)
+        }
+        #[cfg(windows)]
+        {
+            use std::os::windows::process::ExitStatusExt;
+            std::process::ExitStatus::from_raw(code as u32)
+        }
+    }
+
+    fn test_usage() -> ResourceUsage {
+        ResourceUsage {
+            user_time: 1.5,
+            system_time: 0.25,
+            max_memory: 2048,
+            ..ResourceUsage::default()
+        }
+    }
+
+    #[test]
+    fn render_format_substitutes_known_specifiers() {
+        let usage = test_usage();
+        let status = test_exit_status(0);
+        let out = render_format("%e %U %S %M", "", 2.0, &usage, &status);
+        assert_eq!(out, "2.00 1.50 0.25 2048");
+    }
+
+    #[test]
+    fn render_format_handles_percent_escape() {
+        let usage = test_usage();
+        let status = test_exit_status(0);
+        assert_eq!(render_format("100%%", "", 1.0, &usage, &status), "100%");
+    }
+
+    #[test]
+    fn render_format_passes_through_unknown_specifier() {
+        let usage = test_usage();
+        let status = test_exit_status(0);
+        assert_eq!(render_format("%q", "", 1.0, &usage, &status), "%q");
+    }
+
+    #[test]
+    fn render_format_handles_trailing_percent_and_backslash() {
+        let usage = test_usage();
+        let status = test_exit_status(0);
+        assert_eq!(render_format("done%", "", 1.0, &usage, &status), "done%");
+        assert_eq!(render_format("done\\", "", 1.0, &usage, &status), "done\\");
+    }
+
+    #[test]
+    fn render_format_handles_newline_and_tab_escapes() {
+        let usage = test_usage();
+        let status = test_exit_status(0);
+        assert_eq!(render_format("a\\nb\\tc", "", 1.0, &usage, &status), "a\nb\tc");
+    }
+
+    #[test]
+    fn render_format_substitutes_command_and_exit_code() {
+        let usage = test_usage();
+        let status = test_exit_status(7);
+        assert_eq!(render_format("%C exit=%x", "echo hi", 1.0, &usage, &status), "echo hi exit=7");
+    }
+
+    #[test]
+    fn format_elapsed_hms_without_hours() {
+        assert_eq!(format_elapsed_hms(65.5), "1:05.50");
+    }
+
+    #[test]
+    fn format_elapsed_hms_with_hours() {
+        assert_eq!(format_elapsed_hms(3661.2), "1:01:01.20");
+    }
+
+    #[test]
+    fn stats_mean_min_max() {
+        let mut stats = Stats::new();
+        for x in [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0] {
+            stats.add(x);
+        }
+        assert_eq!(stats.n, 8);
+        assert!((stats.mean - 5.0).abs() < 1e-9);
+        assert_eq!(stats.min, 2.0);
+        assert_eq!(stats.max, 9.0);
+        // Sample (n-1) stddev of this set is ~2.13809.
+        assert!((stats.stddev() - 2.138089935299395).abs() < 1e-9);
+    }
+
+    #[test]
+    fn stats_stddev_is_zero_for_single_sample() {
+        let mut stats = Stats::new();
+        stats.add(3.0);
+        assert_eq!(stats.stddev(), 0.0);
+    }
+}